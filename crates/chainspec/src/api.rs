@@ -2,13 +2,32 @@ use crate::{ChainSpec, DepositContract};
 use alloc::{boxed::Box, vec::Vec};
 use alloy_chains::Chain;
 use alloy_consensus::{BlockHeader, Header};
-use alloy_eips::{calc_next_block_base_fee, eip1559::BaseFeeParams, eip7840::BlobParams};
+use alloy_eips::{
+    calc_next_block_base_fee, eip1559::BaseFeeParams, eip4844::GAS_PER_BLOB, eip7840::BlobParams,
+};
 use alloy_genesis::Genesis;
 use alloy_primitives::{B256, U256};
 use core::fmt::{Debug, Display};
 use reth_ethereum_forks::EthereumHardforks;
 use reth_network_peers::NodeRecord;
 
+/// The minimum base fee for blob gas, see [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844).
+const MIN_BASE_FEE_PER_BLOB_GAS: u128 = 1;
+
+/// Approximates `factor * e^(numerator / denominator)` using the truncated Taylor expansion
+/// specified by [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844) for blob base fees.
+const fn fake_exponential(factor: u128, numerator: u128, denominator: u128) -> u128 {
+    let mut i = 1;
+    let mut output = 0;
+    let mut accum = factor * denominator;
+    while accum > 0 {
+        output += accum;
+        accum = accum * numerator / (denominator * i);
+        i += 1;
+    }
+    output / denominator
+}
+
 /// Trait representing type configuring a chain spec.
 #[auto_impl::auto_impl(&, Arc)]
 pub trait EthChainSpec: Send + Sync + Unpin + Debug {
@@ -75,6 +94,180 @@ pub trait EthChainSpec: Send + Sync + Unpin + Debug {
             self.base_fee_params_at_timestamp(target_timestamp),
         ))
     }
+
+    /// Projects the base-fee-per-gas and gas-used-ratio series an `eth_feeHistory`-style oracle
+    /// needs, starting from `parent` and iterating [`calc_next_block_base_fee`] across
+    /// `projected_blocks`, a sequence of `(timestamp, gas_used, gas_limit)` triples for the
+    /// blocks following `parent`. The timestamp of each triple is used to look up the
+    /// [`BaseFeeParams`] active for that block, so a projection window crossing a hardfork
+    /// boundary picks up the new params as soon as it does.
+    ///
+    /// Returns `None` if `parent` has no base fee, i.e. is pre-London. On success, the returned
+    /// base-fee-per-gas series has length `projected_blocks.len() + 1`, starting with `parent`'s
+    /// base fee, and the gas-used-ratio series has length `projected_blocks.len()`.
+    fn fee_history(
+        &self,
+        parent: &Self::Header,
+        projected_blocks: &[(u64, u64, u64)],
+    ) -> Option<(Vec<u64>, Vec<f64>)> {
+        let mut base_fee_per_gas = Vec::with_capacity(projected_blocks.len() + 1);
+        let mut gas_used_ratio = Vec::with_capacity(projected_blocks.len());
+
+        let mut base_fee = parent.base_fee_per_gas()?;
+        base_fee_per_gas.push(base_fee);
+
+        for &(timestamp, gas_used, gas_limit) in projected_blocks {
+            base_fee = calc_next_block_base_fee(
+                gas_used,
+                gas_limit,
+                base_fee,
+                self.base_fee_params_at_timestamp(timestamp),
+            );
+
+            base_fee_per_gas.push(base_fee);
+            gas_used_ratio.push(gas_used as f64 / gas_limit as f64);
+        }
+
+        Some((base_fee_per_gas, gas_used_ratio))
+    }
+
+    /// Returns the weak-subjectivity checkpoint configured for this chain, if any.
+    ///
+    /// There's no checkpoint without being told one: the base [`EthChainSpec`] has nothing to
+    /// source it from. Wrap a spec in [`WithSyncCheckpoint`] to get one, either from an explicit
+    /// override (e.g. a CLI flag) or, failing that, falling back to the chain's genesis block.
+    fn sync_checkpoint(&self) -> Option<ChainCheckpoint> {
+        None
+    }
+
+    /// Computes the child block's blob base fee, in wei per unit of blob gas.
+    ///
+    /// Derives the child's excess blob gas from `parent` and the target blob gas of the
+    /// [`BlobParams`] active at `target_timestamp`, then feeds it through the EIP-4844 fake
+    /// exponential. Returns `None` if `parent` has no blob gas fields, i.e. is pre-Cancun, or if
+    /// `target_timestamp` has no scheduled [`BlobParams`].
+    fn next_block_blob_base_fee(
+        &self,
+        parent: &Self::Header,
+        target_timestamp: u64,
+    ) -> Option<u128> {
+        let blob_params = self.blob_params_at_timestamp(target_timestamp)?;
+        let target_blob_gas = blob_params.target_blob_count * GAS_PER_BLOB;
+
+        let excess_blob_gas =
+            (parent.excess_blob_gas()? + parent.blob_gas_used()?).saturating_sub(target_blob_gas);
+
+        Some(fake_exponential(
+            MIN_BASE_FEE_PER_BLOB_GAS,
+            excess_blob_gas as u128,
+            blob_params.update_fraction,
+        ))
+    }
+}
+
+/// A trusted `(block number, block hash)` weak-subjectivity checkpoint, used so a node syncing
+/// from scratch has a known block to validate the chain it's being served against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainCheckpoint {
+    /// The checkpoint block's number.
+    pub block_number: u64,
+    /// The checkpoint block's hash.
+    pub block_hash: B256,
+}
+
+/// Wraps an [`EthChainSpec`] with a weak-subjectivity [`ChainCheckpoint`], so
+/// [`sync_checkpoint`](EthChainSpec::sync_checkpoint) returns `Some` instead of the base trait's
+/// default `None`.
+///
+/// The checkpoint comes from an explicit override, when one is given (e.g. a CLI flag threaded
+/// through by the caller); otherwise it falls back to the wrapped spec's own genesis block, which
+/// is always a valid, if conservative, weak-subjectivity checkpoint.
+///
+/// Every other [`EthChainSpec`] method delegates straight to the wrapped spec.
+#[derive(Debug, Clone, Copy)]
+pub struct WithSyncCheckpoint<T> {
+    inner: T,
+    r#override: Option<ChainCheckpoint>,
+}
+
+impl<T: EthChainSpec> WithSyncCheckpoint<T> {
+    /// Wraps `inner` with no override; `sync_checkpoint` falls back to `inner`'s genesis block.
+    pub const fn new(inner: T) -> Self {
+        Self { inner, r#override: None }
+    }
+
+    /// Wraps `inner`, reporting `checkpoint` from `sync_checkpoint` instead of falling back to
+    /// genesis. Intended for a caller-supplied override, e.g. parsed from a CLI flag.
+    pub const fn with_override(inner: T, checkpoint: ChainCheckpoint) -> Self {
+        Self { inner, r#override: Some(checkpoint) }
+    }
+}
+
+impl<T: EthChainSpec> EthChainSpec for WithSyncCheckpoint<T> {
+    type Header = T::Header;
+
+    fn chain(&self) -> Chain {
+        self.inner.chain()
+    }
+
+    fn base_fee_params_at_block(&self, block_number: u64) -> BaseFeeParams {
+        self.inner.base_fee_params_at_block(block_number)
+    }
+
+    fn base_fee_params_at_timestamp(&self, timestamp: u64) -> BaseFeeParams {
+        self.inner.base_fee_params_at_timestamp(timestamp)
+    }
+
+    fn blob_params_at_timestamp(&self, timestamp: u64) -> Option<BlobParams> {
+        self.inner.blob_params_at_timestamp(timestamp)
+    }
+
+    fn deposit_contract(&self) -> Option<&DepositContract> {
+        self.inner.deposit_contract()
+    }
+
+    fn genesis_hash(&self) -> B256 {
+        self.inner.genesis_hash()
+    }
+
+    fn prune_delete_limit(&self) -> usize {
+        self.inner.prune_delete_limit()
+    }
+
+    fn display_hardforks(&self) -> Box<dyn Display> {
+        self.inner.display_hardforks()
+    }
+
+    fn genesis_header(&self) -> &Self::Header {
+        self.inner.genesis_header()
+    }
+
+    fn genesis(&self) -> &Genesis {
+        self.inner.genesis()
+    }
+
+    fn bootnodes(&self) -> Option<Vec<NodeRecord>> {
+        self.inner.bootnodes()
+    }
+
+    fn is_optimism(&self) -> bool {
+        self.inner.is_optimism()
+    }
+
+    fn is_ethereum(&self) -> bool {
+        self.inner.is_ethereum()
+    }
+
+    fn final_paris_total_difficulty(&self) -> Option<U256> {
+        self.inner.final_paris_total_difficulty()
+    }
+
+    fn sync_checkpoint(&self) -> Option<ChainCheckpoint> {
+        Some(self.r#override.unwrap_or(ChainCheckpoint {
+            block_number: 0,
+            block_hash: self.inner.genesis_hash(),
+        }))
+    }
 }
 
 impl EthChainSpec for ChainSpec {
@@ -142,3 +335,212 @@ impl EthChainSpec for ChainSpec {
         self.paris_block_and_final_difficulty.map(|(_, final_difficulty)| final_difficulty)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal [`EthChainSpec`] implementor for exercising default trait methods without a
+    /// full [`ChainSpec`].
+    #[derive(Debug)]
+    struct MockChainSpec {
+        base_fee_params: BaseFeeParams,
+        blob_params: BlobParams,
+        genesis: Genesis,
+        genesis_header: Header,
+    }
+
+    impl Default for MockChainSpec {
+        fn default() -> Self {
+            Self {
+                base_fee_params: BaseFeeParams {
+                    max_change_denominator: 8,
+                    elasticity_multiplier: 2,
+                },
+                blob_params: BlobParams {
+                    target_blob_count: 3,
+                    max_blob_count: 6,
+                    update_fraction: 3_338_477,
+                },
+                genesis: Genesis::default(),
+                genesis_header: Header::default(),
+            }
+        }
+    }
+
+    impl EthChainSpec for MockChainSpec {
+        type Header = Header;
+
+        fn chain(&self) -> Chain {
+            Chain::mainnet()
+        }
+
+        fn base_fee_params_at_block(&self, _block_number: u64) -> BaseFeeParams {
+            self.base_fee_params
+        }
+
+        fn base_fee_params_at_timestamp(&self, _timestamp: u64) -> BaseFeeParams {
+            self.base_fee_params
+        }
+
+        fn blob_params_at_timestamp(&self, _timestamp: u64) -> Option<BlobParams> {
+            Some(self.blob_params)
+        }
+
+        fn deposit_contract(&self) -> Option<&DepositContract> {
+            None
+        }
+
+        fn genesis_hash(&self) -> B256 {
+            B256::ZERO
+        }
+
+        fn prune_delete_limit(&self) -> usize {
+            0
+        }
+
+        fn display_hardforks(&self) -> Box<dyn Display> {
+            Box::new("mock")
+        }
+
+        fn genesis_header(&self) -> &Self::Header {
+            &self.genesis_header
+        }
+
+        fn genesis(&self) -> &Genesis {
+            &self.genesis
+        }
+
+        fn bootnodes(&self) -> Option<Vec<NodeRecord>> {
+            None
+        }
+
+        fn final_paris_total_difficulty(&self) -> Option<U256> {
+            None
+        }
+    }
+
+    #[test]
+    fn fee_history_unchanged_at_target_gas() {
+        let spec = MockChainSpec::default();
+        let parent = Header { base_fee_per_gas: Some(100), ..Default::default() };
+
+        let (base_fee_per_gas, gas_used_ratio) =
+            spec.fee_history(&parent, &[(1, 15_000_000, 30_000_000)]).unwrap();
+
+        assert_eq!(base_fee_per_gas, vec![100, 100]);
+        assert_eq!(gas_used_ratio, vec![0.5]);
+    }
+
+    #[test]
+    fn fee_history_rises_above_target_gas() {
+        let spec = MockChainSpec::default();
+        let parent = Header { base_fee_per_gas: Some(100), ..Default::default() };
+
+        let (base_fee_per_gas, _) =
+            spec.fee_history(&parent, &[(1, 30_000_000, 30_000_000)]).unwrap();
+
+        assert_eq!(base_fee_per_gas, vec![100, 112]);
+    }
+
+    #[test]
+    fn fee_history_falls_below_target_gas() {
+        let spec = MockChainSpec::default();
+        let parent = Header { base_fee_per_gas: Some(100), ..Default::default() };
+
+        let (base_fee_per_gas, _) = spec.fee_history(&parent, &[(1, 0, 30_000_000)]).unwrap();
+
+        assert_eq!(base_fee_per_gas, vec![100, 88]);
+    }
+
+    #[test]
+    fn fee_history_compounds_across_projected_blocks() {
+        let spec = MockChainSpec::default();
+        let parent = Header { base_fee_per_gas: Some(100), ..Default::default() };
+
+        // Same full load for two consecutive blocks: each step raises the previous step's base
+        // fee by 1/8, so the second step starts from 112 rather than 100.
+        let (base_fee_per_gas, _) = spec
+            .fee_history(&parent, &[(1, 30_000_000, 30_000_000), (2, 30_000_000, 30_000_000)])
+            .unwrap();
+
+        assert_eq!(base_fee_per_gas, vec![100, 112, 126]);
+    }
+
+    #[test]
+    fn fee_history_returns_none_pre_london() {
+        let spec = MockChainSpec::default();
+        let parent = Header::default();
+
+        assert!(spec.fee_history(&parent, &[(1, 0, 30_000_000)]).is_none());
+    }
+
+    #[test]
+    fn next_block_blob_base_fee_is_min_at_zero_excess() {
+        let spec = MockChainSpec::default();
+        let parent =
+            Header { excess_blob_gas: Some(0), blob_gas_used: Some(0), ..Default::default() };
+
+        assert_eq!(spec.next_block_blob_base_fee(&parent, 1), Some(1));
+    }
+
+    #[test]
+    fn next_block_blob_base_fee_grows_with_excess_blob_gas() {
+        let spec = MockChainSpec::default();
+        // target_blob_gas = target_blob_count(3) * GAS_PER_BLOB(131_072) = 393_216, so this
+        // parent carries 5_000_000 of excess blob gas into the child block.
+        let parent = Header {
+            excess_blob_gas: Some(5_000_000),
+            blob_gas_used: Some(393_216),
+            ..Default::default()
+        };
+
+        assert_eq!(spec.next_block_blob_base_fee(&parent, 1), Some(4));
+    }
+
+    #[test]
+    fn next_block_blob_base_fee_is_none_pre_cancun() {
+        let spec = MockChainSpec::default();
+        let parent = Header::default();
+
+        assert_eq!(spec.next_block_blob_base_fee(&parent, 1), None);
+    }
+
+    #[test]
+    fn fake_exponential_matches_eip4844_reference_vectors() {
+        assert_eq!(fake_exponential(1, 0, 1), 1);
+        assert_eq!(fake_exponential(1, 1, 1), 2);
+        assert_eq!(fake_exponential(1, 2, 1), 6);
+    }
+
+    #[test]
+    fn sync_checkpoint_is_none_without_the_wrapper() {
+        assert_eq!(MockChainSpec::default().sync_checkpoint(), None);
+    }
+
+    #[test]
+    fn with_sync_checkpoint_falls_back_to_genesis() {
+        let spec = WithSyncCheckpoint::new(MockChainSpec::default());
+
+        assert_eq!(
+            spec.sync_checkpoint(),
+            Some(ChainCheckpoint { block_number: 0, block_hash: spec.genesis_hash() })
+        );
+    }
+
+    #[test]
+    fn with_sync_checkpoint_prefers_the_override() {
+        let checkpoint = ChainCheckpoint { block_number: 123, block_hash: B256::repeat_byte(0xab) };
+        let spec = WithSyncCheckpoint::with_override(MockChainSpec::default(), checkpoint);
+
+        assert_eq!(spec.sync_checkpoint(), Some(checkpoint));
+    }
+
+    #[test]
+    fn with_sync_checkpoint_delegates_other_methods_to_the_wrapped_spec() {
+        let spec = WithSyncCheckpoint::new(MockChainSpec::default());
+
+        assert_eq!(spec.chain(), MockChainSpec::default().chain());
+        assert_eq!(spec.prune_delete_limit(), MockChainSpec::default().prune_delete_limit());
+    }
+}
@@ -1,12 +1,19 @@
 use std::{
+    collections::{HashMap, HashSet},
     ops::RangeInclusive,
     pin::Pin,
     task::{ready, Context, Poll},
 };
 
-use crate::{download::DownloadClient, error::PeerRequestResult, priority::Priority};
+use crate::{
+    download::DownloadClient,
+    error::{PeerRequestResult, WithPeerId},
+    priority::Priority,
+};
+use alloy_consensus::BlockHeader;
 use alloy_primitives::B256;
 use futures::{Future, FutureExt};
+use reth_network_peers::PeerId;
 use reth_primitives_traits::BlockBody;
 
 /// The bodies future type
@@ -83,3 +90,300 @@ where
         Poll::Ready(resp)
     }
 }
+
+/// The transactions root, ommers hash, and withdrawals root that a body and the header it
+/// belongs to must agree on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RootTriple {
+    transactions_root: B256,
+    ommers_hash: B256,
+    withdrawals_root: Option<B256>,
+}
+
+impl RootTriple {
+    fn of_body<B: BlockBody>(body: &B) -> Self {
+        Self {
+            transactions_root: body.calculate_tx_root(),
+            ommers_hash: body.calculate_ommers_root(),
+            withdrawals_root: body.calculate_withdrawals_root(),
+        }
+    }
+
+    fn of_header<H: BlockHeader>(header: &H) -> Self {
+        Self {
+            transactions_root: header.transactions_root(),
+            ommers_hash: header.ommers_hash(),
+            withdrawals_root: header.withdrawals_root(),
+        }
+    }
+}
+
+/// The result of matching a peer's body response back to the headers it was requested for.
+struct MatchedBodies<B> {
+    /// Bodies that matched their expected header's roots, alongside that header's hash.
+    verified: Vec<(B256, B)>,
+    /// Hashes whose body is still outstanding: either the peer didn't return it, or the body
+    /// returned for it didn't match any remaining expected header.
+    unresolved: HashSet<B256>,
+    /// Whether at least one returned body didn't match any outstanding header, as opposed to
+    /// simply being withheld by the peer.
+    had_mismatch: bool,
+}
+
+/// Matches a peer's body response back to the headers whose hashes were requested.
+///
+/// Bodies are returned in request order, but per the wire protocol a peer may omit any body it
+/// doesn't have while keeping the rest in their relative order. That means the body at response
+/// index `i` does not necessarily belong to the header at request index `i`, so this can't just
+/// zip the two lists together. Instead each body is matched against the next outstanding header
+/// (starting from a cursor into `expected`) whose roots it satisfies: any outstanding header
+/// skipped to get there is treated as withheld rather than mismatched, and the cursor advances
+/// past the match. A body that doesn't satisfy any header from the cursor onward is a genuine
+/// mismatch; the cursor is left where it was so a later body in the same response still gets a
+/// chance at those headers.
+fn match_bodies_to_headers<B>(
+    bodies: Vec<B>,
+    body_roots: Vec<RootTriple>,
+    expected: &[(B256, RootTriple)],
+) -> MatchedBodies<B> {
+    let mut verified = Vec::with_capacity(bodies.len());
+    let mut unresolved = HashSet::new();
+    let mut had_mismatch = false;
+
+    let mut cursor = 0;
+    for (body, roots) in bodies.into_iter().zip(body_roots) {
+        match expected[cursor..].iter().position(|(_, expected_roots)| *expected_roots == roots) {
+            Some(offset) => {
+                unresolved.extend(expected[cursor..cursor + offset].iter().map(|(hash, _)| *hash));
+                let (hash, _) = expected[cursor + offset];
+                verified.push((hash, body));
+                cursor += offset + 1;
+            }
+            None => had_mismatch = true,
+        }
+    }
+    unresolved.extend(expected[cursor..].iter().map(|(hash, _)| *hash));
+
+    MatchedBodies { verified, unresolved, had_mismatch }
+}
+
+/// The maximum number of times [`VerifyingBodiesClient::get_verified_block_bodies`] will
+/// request a hash, counting the first request, before giving up on it.
+const MAX_VERIFICATION_ATTEMPTS: usize = 3;
+
+/// The future returned by [`VerifyingBodiesClient::get_verified_block_bodies`], resolving to the
+/// verified `(hash, body)` pairs in the same order as the `headers` the request was built from.
+pub type VerifiedBodiesFut<B> =
+    Pin<Box<dyn Future<Output = PeerRequestResult<Vec<(B256, B)>>> + Send + Sync>>;
+
+/// A [`BodiesClient`] wrapper that verifies every returned body against the header it was
+/// requested for.
+///
+/// A [`BodiesClient`] only has the requested hashes to go on, so a malicious or buggy peer can
+/// answer a `GetBlockBodies` request with a body that doesn't belong to the header downloaded
+/// for that hash. [`VerifyingBodiesClient::get_verified_block_bodies`] recomputes each body's
+/// `transactions_root`, `ommers_hash`, and `withdrawals_root` and compares them against the
+/// header that was already validated during header sync, scoring down the offending peer
+/// whenever a returned body doesn't match and re-requesting the unresolved hashes through the
+/// inner client ([`MAX_VERIFICATION_ATTEMPTS`] requests in total). The inner client picks the
+/// peer for each request; the peer that sent a bad body is not excluded from the next one.
+///
+/// [`VerifyingBodiesClient`] also implements [`BodiesClient`] itself by delegating to the inner
+/// client unverified, so it can be used as a drop-in [`BodiesClient`] anywhere one is expected;
+/// callers that have the corresponding headers on hand should prefer
+/// [`get_verified_block_bodies`](Self::get_verified_block_bodies) instead.
+#[derive(Debug, Clone)]
+pub struct VerifyingBodiesClient<C> {
+    client: C,
+}
+
+impl<C> VerifyingBodiesClient<C> {
+    /// Wraps `client` with body/header root verification.
+    pub const fn new(client: C) -> Self {
+        Self { client }
+    }
+}
+
+impl<C> VerifyingBodiesClient<C>
+where
+    C: BodiesClient + Clone + Send + Sync + 'static,
+{
+    /// Fetches the bodies for `headers`, verifying each one against the `transactions_root`,
+    /// `ommers_hash`, and `withdrawals_root` of its paired header before returning it.
+    ///
+    /// Any hash whose body fails verification is re-requested, for up to
+    /// [`MAX_VERIFICATION_ATTEMPTS`] requests in total counting the first, with the offending
+    /// peer scored down after each attempt that returned a mismatching body. Hashes still unverified after the final attempt are dropped from the
+    /// result. The result is always in the same order as `headers`, regardless of which attempt
+    /// resolved each hash.
+    pub fn get_verified_block_bodies<H>(
+        &self,
+        headers: Vec<(B256, H)>,
+        priority: Priority,
+    ) -> VerifiedBodiesFut<C::Body>
+    where
+        H: BlockHeader,
+    {
+        let client = self.client.clone();
+        let order: HashMap<B256, usize> =
+            headers.iter().enumerate().map(|(index, (hash, _))| (*hash, index)).collect();
+        let mut remaining: Vec<(B256, RootTriple)> =
+            headers.iter().map(|(hash, header)| (*hash, RootTriple::of_header(header))).collect();
+
+        Box::pin(async move {
+            let mut slots: Vec<Option<(B256, C::Body)>> =
+                (0..remaining.len()).map(|_| None).collect();
+            let mut last_peer_id = PeerId::default();
+
+            for _ in 0..MAX_VERIFICATION_ATTEMPTS {
+                if remaining.is_empty() {
+                    break;
+                }
+
+                let hashes = remaining.iter().map(|(hash, _)| *hash).collect();
+                let resp = client.get_block_bodies_with_priority(hashes, priority).await?;
+                let (peer_id, bodies) = resp.split();
+                last_peer_id = peer_id;
+
+                let body_roots = bodies.iter().map(RootTriple::of_body).collect();
+                let matched = match_bodies_to_headers(bodies, body_roots, &remaining);
+
+                if matched.had_mismatch {
+                    client.report_bad_message(peer_id);
+                }
+
+                fill_in_header_order(&mut slots, &order, matched.verified);
+                remaining.retain(|(hash, _)| matched.unresolved.contains(hash));
+            }
+
+            Ok(WithPeerId::new(last_peer_id, slots.into_iter().flatten().collect()))
+        })
+    }
+}
+
+/// Writes each `(hash, body)` pair into `slots` at the position `order` recorded for that hash,
+/// so that bodies verified across several retry rounds still end up in the original header order
+/// once every round has written its slots.
+fn fill_in_header_order<B>(
+    slots: &mut [Option<(B256, B)>],
+    order: &HashMap<B256, usize>,
+    newly_verified: Vec<(B256, B)>,
+) {
+    for (hash, body) in newly_verified {
+        slots[order[&hash]] = Some((hash, body));
+    }
+}
+
+impl<C: DownloadClient> DownloadClient for VerifyingBodiesClient<C> {
+    fn report_bad_message(&self, peer_id: PeerId) {
+        self.client.report_bad_message(peer_id)
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        self.client.num_connected_peers()
+    }
+}
+
+impl<C> BodiesClient for VerifyingBodiesClient<C>
+where
+    C: BodiesClient,
+{
+    type Body = C::Body;
+    type Output = C::Output;
+
+    fn get_block_bodies_with_priority_and_range_hint(
+        &self,
+        hashes: Vec<B256>,
+        priority: Priority,
+        range_hint: Option<RangeInclusive<u64>>,
+    ) -> Self::Output {
+        self.client.get_block_bodies_with_priority_and_range_hint(hashes, priority, range_hint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triple(n: u8) -> RootTriple {
+        RootTriple {
+            transactions_root: B256::repeat_byte(n),
+            ommers_hash: B256::repeat_byte(n),
+            withdrawals_root: None,
+        }
+    }
+
+    fn hash(n: u8) -> B256 {
+        B256::repeat_byte(n)
+    }
+
+    #[test]
+    fn matches_full_response_in_order() {
+        let expected = vec![(hash(1), triple(1)), (hash(2), triple(2)), (hash(3), triple(3))];
+        let bodies = vec!["a", "b", "c"];
+        let body_roots = vec![triple(1), triple(2), triple(3)];
+
+        let matched = match_bodies_to_headers(bodies, body_roots, &expected);
+
+        assert_eq!(matched.verified, vec![(hash(1), "a"), (hash(2), "b"), (hash(3), "c")]);
+        assert!(matched.unresolved.is_empty());
+        assert!(!matched.had_mismatch);
+    }
+
+    #[test]
+    fn treats_an_omitted_body_as_withheld_not_mismatched() {
+        // the peer doesn't have the body for hash(2) and simply skips it, keeping the others in
+        // order.
+        let expected = vec![(hash(1), triple(1)), (hash(2), triple(2)), (hash(3), triple(3))];
+        let bodies = vec!["a", "c"];
+        let body_roots = vec![triple(1), triple(3)];
+
+        let matched = match_bodies_to_headers(bodies, body_roots, &expected);
+
+        assert_eq!(matched.verified, vec![(hash(1), "a"), (hash(3), "c")]);
+        assert_eq!(matched.unresolved, HashSet::from([hash(2)]));
+        assert!(!matched.had_mismatch);
+    }
+
+    #[test]
+    fn flags_a_body_that_matches_no_outstanding_header() {
+        let expected = vec![(hash(1), triple(1)), (hash(2), triple(2))];
+        let bodies = vec!["a", "x"];
+        let body_roots = vec![triple(1), triple(9)];
+
+        let matched = match_bodies_to_headers(bodies, body_roots, &expected);
+
+        assert_eq!(matched.verified, vec![(hash(1), "a")]);
+        assert!(matched.unresolved.contains(&hash(2)));
+        assert!(matched.had_mismatch);
+    }
+
+    #[test]
+    fn a_mismatch_does_not_poison_matching_for_later_bodies() {
+        // body(1) matches h1, bodyX matches nothing, body(3) matches h3. The mismatch on bodyX
+        // must not consume h2/h3 from the cursor, or body(3)'s genuine match to h3 would never
+        // even be attempted.
+        let expected = vec![(hash(1), triple(1)), (hash(2), triple(2)), (hash(3), triple(3))];
+        let bodies = vec!["body1", "bodyX", "body3"];
+        let body_roots = vec![triple(1), triple(9), triple(3)];
+
+        let matched = match_bodies_to_headers(bodies, body_roots, &expected);
+
+        assert_eq!(matched.verified, vec![(hash(1), "body1"), (hash(3), "body3")]);
+        assert_eq!(matched.unresolved, HashSet::from([hash(2)]));
+        assert!(matched.had_mismatch);
+    }
+
+    #[test]
+    fn fill_in_header_order_preserves_header_order_across_rounds() {
+        let order = HashMap::from([(hash(1), 0), (hash(2), 1), (hash(3), 2)]);
+        let mut slots: Vec<Option<(B256, &str)>> = vec![None, None, None];
+
+        // First round resolves hash(1) and hash(3); a retry round later resolves hash(2).
+        fill_in_header_order(&mut slots, &order, vec![(hash(1), "a"), (hash(3), "c")]);
+        fill_in_header_order(&mut slots, &order, vec![(hash(2), "b")]);
+
+        let result: Vec<_> = slots.into_iter().flatten().collect();
+        assert_eq!(result, vec![(hash(1), "a"), (hash(2), "b"), (hash(3), "c")]);
+    }
+}
@@ -2,23 +2,70 @@ use crate::{
     segments::{PruneInput, Segment},
     PrunerError,
 };
-use reth_db_api::{table::Value, transaction::DbTxMut};
-use reth_primitives_traits::NodePrimitives;
+use alloy_primitives::{BlockNumber, Bloom};
+use reth_codecs::Compact;
+use reth_db_api::{
+    table::{Table, Value},
+    transaction::DbTxMut,
+};
+use reth_primitives_traits::{NodePrimitives, Receipt as _};
 use reth_provider::{
     errors::provider::ProviderResult, BlockReader, DBProvider, NodePrimitivesProvider,
-    PruneCheckpointWriter, TransactionsProvider,
+    PruneCheckpointWriter, ReceiptProvider, TransactionsProvider,
 };
 use reth_prune_types::{PruneCheckpoint, PruneMode, PrunePurpose, PruneSegment, SegmentOutput};
 use tracing::instrument;
 
+/// A persisted logs-bloom index entry for one pruned block range.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Compact)]
+pub struct ReceiptsBloomRangeIndex {
+    /// Bitwise OR of every receipt's logs bloom across the range.
+    pub aggregated: Bloom,
+    /// Per-block aggregated blooms within the range, in block order, so a query can be narrowed
+    /// to a single block without reading its (pruned) receipts.
+    pub per_block: Vec<Bloom>,
+}
+
+/// The dedicated table backing the logs-bloom index, keyed by the first block number of an
+/// aggregated range. See [`ReceiptsBloomRangeIndex`].
+///
+/// Note: this `Table` impl is necessary but not sufficient for the table to exist in the
+/// database — it also needs an entry in the `tables!` declaration that `reth_db_api` generates
+/// `Tables`/`TableSet` from. That declaration isn't part of this crate and isn't present in this
+/// checkout, so it isn't wired up here; add `ReceiptsBloomIndex` to it before shipping a database
+/// that relies on this table existing.
+#[derive(Debug)]
+pub struct ReceiptsBloomIndex;
+
+impl Table for ReceiptsBloomIndex {
+    const NAME: &'static str = "ReceiptsBloomIndex";
+    type Key = BlockNumber;
+    type Value = ReceiptsBloomRangeIndex;
+}
+
 #[derive(Debug)]
 pub struct Receipts {
     mode: PruneMode,
+    /// When `true`, a compact per-range logs-bloom index is persisted for a block range before
+    /// its receipts are deleted, so `eth_getLogs` can keep filtering ranges that have been
+    /// pruned.
+    retain_logs_bloom_index: bool,
 }
 
 impl Receipts {
+    /// Creates a new [`Receipts`] prune segment with the logs-bloom index disabled.
+    ///
+    /// Use [`with_logs_bloom_index`](Self::with_logs_bloom_index) to enable it.
     pub const fn new(mode: PruneMode) -> Self {
-        Self { mode }
+        Self { mode, retain_logs_bloom_index: false }
+    }
+
+    /// Sets whether a compact per-range logs-bloom index is persisted for a block range before
+    /// its receipts are deleted. The index holds blooms only, so it can rule a pruned range out
+    /// of a log query but cannot return that range's logs.
+    pub const fn with_logs_bloom_index(mut self, retain_logs_bloom_index: bool) -> Self {
+        self.retain_logs_bloom_index = retain_logs_bloom_index;
+        self
     }
 }
 
@@ -28,7 +75,9 @@ where
         + PruneCheckpointWriter
         + TransactionsProvider
         + BlockReader
-        + NodePrimitivesProvider<Primitives: NodePrimitives<Receipt: Value>>,
+        + NodePrimitivesProvider<
+            Primitives: NodePrimitives<Receipt: Value + reth_primitives_traits::Receipt>,
+        > + ReceiptProvider<Receipt = <Provider::Primitives as NodePrimitives>::Receipt>,
 {
     fn segment(&self) -> PruneSegment {
         PruneSegment::Receipts
@@ -44,6 +93,10 @@ where
 
     #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
     fn prune(&self, provider: &Provider, input: PruneInput) -> Result<SegmentOutput, PrunerError> {
+        if self.retain_logs_bloom_index {
+            index_logs_bloom(provider, &input)?;
+        }
+
         crate::segments::receipts::prune(provider, input)
     }
 
@@ -55,3 +108,64 @@ where
         crate::segments::receipts::save_checkpoint(provider, checkpoint)
     }
 }
+
+/// Extracts each receipt's logs bloom for the block range `input` is about to prune and persists
+/// a compact per-range bloom index keyed by the range's first block, before the range's receipts
+/// are deleted.
+fn index_logs_bloom<Provider>(provider: &Provider, input: &PruneInput) -> Result<(), PrunerError>
+where
+    Provider: DBProvider<Tx: DbTxMut>
+        + BlockReader
+        + NodePrimitivesProvider<
+            Primitives: NodePrimitives<Receipt: Value + reth_primitives_traits::Receipt>,
+        > + ReceiptProvider<Receipt = <Provider::Primitives as NodePrimitives>::Receipt>,
+{
+    let Some(range) = input.get_next_block_range(provider)? else { return Ok(()) };
+
+    let mut per_block = Vec::with_capacity(range.clone().count());
+    for block_number in range.clone() {
+        let receipts = provider.receipts_by_block(block_number.into())?.unwrap_or_default();
+        per_block.push(aggregate_blooms(receipts.iter().map(|receipt| receipt.bloom())));
+    }
+    let aggregated = aggregate_blooms(per_block.iter().copied());
+
+    provider
+        .tx_ref()
+        .put::<ReceiptsBloomIndex>(
+            *range.start(),
+            ReceiptsBloomRangeIndex { aggregated, per_block },
+        )
+        .map_err(Into::into)
+}
+
+/// Bitwise-ORs a sequence of blooms into one.
+fn aggregate_blooms(blooms: impl IntoIterator<Item = Bloom>) -> Bloom {
+    let mut aggregated = Bloom::ZERO;
+    for bloom in blooms {
+        aggregated.accrue_bloom(&bloom);
+    }
+    aggregated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_blooms_is_bitwise_or_of_inputs() {
+        let mut a = Bloom::ZERO;
+        a.accrue(alloy_primitives::bloom::BloomInput::Raw(b"a"));
+        let mut b = Bloom::ZERO;
+        b.accrue(alloy_primitives::bloom::BloomInput::Raw(b"b"));
+
+        let mut expected = a;
+        expected.accrue_bloom(&b);
+
+        assert_eq!(aggregate_blooms([a, b]), expected);
+    }
+
+    #[test]
+    fn aggregate_blooms_of_empty_input_is_zero() {
+        assert_eq!(aggregate_blooms([]), Bloom::ZERO);
+    }
+}